@@ -0,0 +1,101 @@
+use std::{fs, path::Path};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{migrations::migration_directory_from_given_path, migrations_directories, version_from_string};
+
+/// Annotation a migration author can put as the first line of `up.sql` (and
+/// `down.sql`) to mark the migration as unable to run inside a transaction,
+/// e.g. for `CREATE INDEX CONCURRENTLY` or `ALTER TYPE ... ADD VALUE`.
+const NO_TRANSACTION_ANNOTATION: &str = "-- diesel_async_migrations:no-transaction";
+
+struct Migration {
+    name: String,
+    up: String,
+    down: Option<String>,
+    run_in_transaction: bool,
+}
+
+pub fn expand(input: String) -> TokenStream {
+    let given_path = parse_path_argument(&input);
+
+    let migrations_dir = migration_directory_from_given_path(given_path.as_deref())
+        .unwrap_or_else(|e| panic!("embed_migrations!: {e}"));
+
+    let mut migrations = read_migrations(&migrations_dir)
+        .unwrap_or_else(|e| panic!("embed_migrations!: failed to read migrations directory: {e}"));
+
+    migrations.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let migrations = migrations.iter().map(|mig| {
+        let name = &mig.name;
+        let up = &mig.up;
+        let down = match &mig.down {
+            Some(down) => quote! { Some(#down) },
+            None => quote! { None },
+        };
+        let run_in_transaction = mig.run_in_transaction;
+
+        quote! {
+            diesel_async_migrations::EmbeddedMigration {
+                name: #name,
+                up: #up,
+                down: #down,
+                run_in_transaction: #run_in_transaction,
+            }
+        }
+    });
+
+    quote! {
+        diesel_async_migrations::EmbeddedMigrations {
+            migrations: &[#(#migrations),*],
+            setup_attempted: ::std::sync::atomic::AtomicU8::new(0),
+        }
+    }
+}
+
+fn parse_path_argument(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.trim_matches('"').to_string())
+    }
+}
+
+fn read_migrations(dir: &Path) -> std::io::Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    for entry in migrations_directories(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if version_from_string(&name).is_none() {
+            continue;
+        }
+
+        let up = fs::read_to_string(entry.path().join("up.sql"))?;
+        let down = fs::read_to_string(entry.path().join("down.sql")).ok();
+
+        let run_in_transaction = !first_line_is_no_transaction_annotation(&up)
+            && !down
+                .as_deref()
+                .is_some_and(first_line_is_no_transaction_annotation);
+
+        migrations.push(Migration {
+            name,
+            up,
+            down,
+            run_in_transaction,
+        });
+    }
+
+    Ok(migrations)
+}
+
+fn first_line_is_no_transaction_annotation(sql: &str) -> bool {
+    sql.lines()
+        .next()
+        .is_some_and(|line| line.trim() == NO_TRANSACTION_ANNOTATION)
+}
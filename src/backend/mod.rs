@@ -0,0 +1,107 @@
+//! Backend-specific pieces of managing the schema migrations table.
+//!
+//! This crate isn't hard-bound to `diesel::pg::Pg`: the parts of schema
+//! migration management that differ by database (mainly the DDL used to
+//! create `__diesel_schema_migrations`) live behind the [`MigrationBackend`]
+//! trait, implemented for each backend gated by its matching Cargo feature
+//! (`postgres`, `mysql`, `sqlite`), mirroring how `diesel_migrations` supports
+//! multiple backends.
+
+use diesel::backend::Backend;
+use diesel_async::AsyncConnection;
+
+use crate::Version;
+
+/// The bits of managing `__diesel_schema_migrations` that differ by database
+/// backend.
+///
+/// Recording and removing rows is part of this trait, not generic code in
+/// `lib.rs`, because it isn't just the DDL that differs: MySQL has no
+/// `RETURNING` clause, so it can't report back the row it just
+/// inserted/deleted the way Postgres and SQLite can, and needs a separate
+/// `SELECT` instead. Keeping these methods on `Self = Backend` (rather than
+/// generic free functions bounded by `C::Backend: MigrationBackend`) also
+/// means each impl is only ever compiled against its own concrete backend,
+/// so the `diesel`/`diesel-async` trait bounds the query builder needs
+/// (`QueryFragment<Self>`, `LoadQuery<'_, C, _>`, ...) are satisfied by
+/// `diesel`'s own per-backend impls instead of having to be reconstructed
+/// generically here.
+pub trait MigrationBackend: Backend {
+    /// SQL executed once per process to create (or upgrade) the schema
+    /// migrations table. Must be idempotent (`IF NOT EXISTS`, or an
+    /// equivalent existence check where the dialect lacks it), since
+    /// multiple instances of this crate may run it against the same,
+    /// already-migrated database.
+    const SETUP_MIGRATIONS_TABLE_SQL: &'static str;
+
+    /// Backfills the `checksum` column onto a `__diesel_schema_migrations`
+    /// table that predates it (e.g. one created by
+    /// diesel-cli/diesel_migrations, which has no such column), for
+    /// backends where [`SETUP_MIGRATIONS_TABLE_SQL`](Self::SETUP_MIGRATIONS_TABLE_SQL)
+    /// can't express that itself.
+    ///
+    /// The default does nothing: Postgres and MySQL handle this as part of
+    /// their setup SQL (`ALTER TABLE ... ADD COLUMN IF NOT EXISTS` on
+    /// Postgres; a `INFORMATION_SCHEMA`-guarded `ALTER TABLE` on MySQL).
+    /// SQLite overrides this, since `ALTER TABLE ... ADD COLUMN` isn't
+    /// idempotent there and SQLite's SQL dialect has no conditional
+    /// statements to guard it with from within a plain SQL script.
+    fn ensure_checksum_column<C>(
+        _conn: &mut C,
+    ) -> impl std::future::Future<Output = diesel::QueryResult<()>> + Send
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        async { Ok(()) }
+    }
+
+    /// Records that `version` was just applied, returning the inserted row.
+    fn insert_version<C>(
+        conn: &mut C,
+        version: String,
+        checksum: Vec<u8>,
+    ) -> impl std::future::Future<Output = diesel::QueryResult<Version>> + Send
+    where
+        C: AsyncConnection<Backend = Self> + Send;
+
+    /// Removes the record of `version` having been applied, returning the
+    /// deleted row.
+    fn delete_version<C>(
+        conn: &mut C,
+        version: &str,
+    ) -> impl std::future::Future<Output = diesel::QueryResult<Version>> + Send
+    where
+        C: AsyncConnection<Backend = Self> + Send;
+
+    /// Returns every applied migration, most recent first.
+    fn applied_migrations<C>(
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = diesel::QueryResult<Vec<Version>>> + Send
+    where
+        C: AsyncConnection<Backend = Self> + Send;
+}
+
+/// Marker for backends whose DDL actually participates in a transaction, so
+/// that rolling the transaction back after a failure undoes it.
+///
+/// Bounds
+/// [`MigrationSource::run_pending_migrations_in_transaction`](crate::MigrationSource::run_pending_migrations_in_transaction),
+/// whose whole point is a roll-back-on-failure guarantee across a batch of
+/// migrations. That guarantee doesn't hold on a backend without this: MySQL
+/// statements like `CREATE TABLE`/`ALTER TABLE` trigger an implicit commit,
+/// so wrapping them in a transaction doesn't make them revertible, and a
+/// migration failing partway through a "single transaction" run would
+/// silently leave the earlier migrations in that batch applied.
+pub trait TransactionalDdl: MigrationBackend {}
+
+#[cfg(feature = "postgres")]
+impl TransactionalDdl for diesel::pg::Pg {}
+#[cfg(feature = "sqlite")]
+impl TransactionalDdl for diesel::sqlite::Sqlite {}
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "mysql")]
+mod mysql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
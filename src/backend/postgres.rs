@@ -0,0 +1,86 @@
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+use crate::{__diesel_schema_migrations, Version};
+
+use super::MigrationBackend;
+
+impl MigrationBackend for Pg {
+    const SETUP_MIGRATIONS_TABLE_SQL: &'static str =
+        include_str!("postgres/setup_migration_table.sql");
+
+    async fn insert_version<C>(
+        conn: &mut C,
+        version: String,
+        checksum: Vec<u8>,
+    ) -> diesel::QueryResult<Version>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let (version, run_on, checksum) = diesel::insert_into(__diesel_schema_migrations::table)
+            .values((
+                __diesel_schema_migrations::version.eq(version),
+                __diesel_schema_migrations::checksum.eq(checksum),
+            ))
+            .returning((
+                __diesel_schema_migrations::version,
+                __diesel_schema_migrations::run_on,
+                __diesel_schema_migrations::checksum,
+            ))
+            .get_result::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+            .await?;
+
+        Ok(Version {
+            version,
+            run_on,
+            checksum,
+        })
+    }
+
+    async fn delete_version<C>(conn: &mut C, version: &str) -> diesel::QueryResult<Version>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let (version, run_on, checksum) =
+            diesel::delete(__diesel_schema_migrations::table.find(version))
+                .returning((
+                    __diesel_schema_migrations::version,
+                    __diesel_schema_migrations::run_on,
+                    __diesel_schema_migrations::checksum,
+                ))
+                .get_result::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+                .await?;
+
+        Ok(Version {
+            version,
+            run_on,
+            checksum,
+        })
+    }
+
+    async fn applied_migrations<C>(conn: &mut C) -> diesel::QueryResult<Vec<Version>>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let res = __diesel_schema_migrations::table
+            .select((
+                __diesel_schema_migrations::version,
+                __diesel_schema_migrations::run_on,
+                __diesel_schema_migrations::checksum,
+            ))
+            .order(__diesel_schema_migrations::version.desc())
+            .get_results::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(version, run_on, checksum)| Version {
+                version,
+                run_on,
+                checksum,
+            })
+            .collect();
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,122 @@
+//! `RETURNING` support landed in SQLite 3.35 and in Diesel behind the
+//! `returning_clauses_for_sqlite_3_35` feature; a `Cargo.toml` enabling the
+//! `sqlite` feature of this crate must also enable that Diesel feature
+//! (`diesel = { features = ["sqlite", "returning_clauses_for_sqlite_3_35"] }`)
+//! for the queries below to compile.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+use crate::{__diesel_schema_migrations, Version};
+
+use super::MigrationBackend;
+
+/// A row of `PRAGMA table_info(...)`, used only to read back column names.
+#[derive(QueryableByName)]
+struct ColumnInfo {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+impl MigrationBackend for Sqlite {
+    const SETUP_MIGRATIONS_TABLE_SQL: &'static str =
+        include_str!("sqlite/setup_migration_table.sql");
+
+    async fn ensure_checksum_column<C>(conn: &mut C) -> diesel::QueryResult<()>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        // SQLite's `ALTER TABLE ... ADD COLUMN` errors if the column already
+        // exists, and plain SQL has no conditional statement to guard it
+        // with (unlike Postgres's `ADD COLUMN IF NOT EXISTS` or MySQL's
+        // `INFORMATION_SCHEMA`-guarded `ALTER`), so the check has to happen
+        // here instead of in `setup_migration_table.sql`.
+        let columns = diesel::sql_query("PRAGMA table_info(__diesel_schema_migrations)")
+            .load::<ColumnInfo>(conn)
+            .await?;
+
+        if !columns.iter().any(|c| c.name == "checksum") {
+            diesel::sql_query("ALTER TABLE __diesel_schema_migrations ADD COLUMN checksum BLOB")
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_version<C>(
+        conn: &mut C,
+        version: String,
+        checksum: Vec<u8>,
+    ) -> diesel::QueryResult<Version>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let (version, run_on, checksum) = diesel::insert_into(__diesel_schema_migrations::table)
+            .values((
+                __diesel_schema_migrations::version.eq(version),
+                __diesel_schema_migrations::checksum.eq(checksum),
+            ))
+            .returning((
+                __diesel_schema_migrations::version,
+                __diesel_schema_migrations::run_on,
+                __diesel_schema_migrations::checksum,
+            ))
+            .get_result::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+            .await?;
+
+        Ok(Version {
+            version,
+            run_on,
+            checksum,
+        })
+    }
+
+    async fn delete_version<C>(conn: &mut C, version: &str) -> diesel::QueryResult<Version>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let (version, run_on, checksum) =
+            diesel::delete(__diesel_schema_migrations::table.find(version))
+                .returning((
+                    __diesel_schema_migrations::version,
+                    __diesel_schema_migrations::run_on,
+                    __diesel_schema_migrations::checksum,
+                ))
+                .get_result::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+                .await?;
+
+        Ok(Version {
+            version,
+            run_on,
+            checksum,
+        })
+    }
+
+    async fn applied_migrations<C>(conn: &mut C) -> diesel::QueryResult<Vec<Version>>
+    where
+        C: AsyncConnection<Backend = Self> + Send,
+    {
+        let res = __diesel_schema_migrations::table
+            .select((
+                __diesel_schema_migrations::version,
+                __diesel_schema_migrations::run_on,
+                __diesel_schema_migrations::checksum,
+            ))
+            .order(__diesel_schema_migrations::version.desc())
+            .get_results::<(String, NaiveDateTime, Option<Vec<u8>>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(version, run_on, checksum)| Version {
+                version,
+                run_on,
+                checksum,
+            })
+            .collect();
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,171 @@
+//! Runtime, directory-based alternative to `embed_migrations!`, for setups
+//! where migrations need to stay editable without recompiling (e.g. mounted
+//! into a container).
+//!
+//! The directory-walking logic below mirrors what
+//! `diesel_async_migrations_macros` does at compile time, but that crate is
+//! `proc-macro = true`, which only allows it to export macros to dependents,
+//! not plain functions — so the helpers are duplicated here rather than
+//! shared.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicU8,
+};
+
+use crate::{Migration, MigrationError, MigrationSource, Result};
+
+/// See [`EmbeddedMigration::run_in_transaction`](crate::EmbeddedMigration::run_in_transaction).
+const NO_TRANSACTION_ANNOTATION: &str = "-- diesel_async_migrations:no-transaction";
+
+/// A single migration discovered on disk at runtime, as opposed to
+/// [`EmbeddedMigration`](crate::EmbeddedMigration), which is baked into the
+/// binary at compile time by `embed_migrations!`.
+#[derive(Debug, Clone)]
+pub struct FileMigration {
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+    pub run_in_transaction: bool,
+}
+
+impl Migration for FileMigration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn up(&self) -> &str {
+        &self.up
+    }
+
+    fn down(&self) -> Option<&str> {
+        self.down.as_deref()
+    }
+
+    fn run_in_transaction(&self) -> bool {
+        self.run_in_transaction
+    }
+}
+
+/// A [`MigrationSource`] that re-reads a directory of migrations from disk
+/// every time it's asked for them, instead of embedding them into the
+/// binary like [`EmbeddedMigrations`](crate::EmbeddedMigrations) does.
+#[derive(Debug)]
+pub struct FileBasedMigrations {
+    directory: PathBuf,
+    setup_attempted: AtomicU8,
+}
+
+impl FileBasedMigrations {
+    /// Uses `path` as the migrations directory.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            directory: path.as_ref().canonicalize()?,
+            setup_attempted: AtomicU8::new(0),
+        })
+    }
+
+    /// Searches for a `migrations` directory the same way `embed_migrations!`
+    /// does: starting from `$CARGO_MANIFEST_DIR/src` and walking up through
+    /// parent directories until one is found.
+    pub fn find_migrations_directory() -> Result<Self> {
+        let cargo_toml_directory = env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+            MigrationError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "CARGO_MANIFEST_DIR is not set",
+            ))
+        })?;
+
+        let src_dir = Path::new(&cargo_toml_directory).join("src");
+
+        let migrations_dir = search_for_migrations_directory(&src_dir).ok_or_else(|| {
+            MigrationError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "failed to find migrations directory in {}",
+                    src_dir.display()
+                ),
+            ))
+        })?;
+
+        Self::from_path(migrations_dir)
+    }
+}
+
+impl MigrationSource for FileBasedMigrations {
+    type Migration = FileMigration;
+
+    fn migrations(&self) -> Result<Vec<FileMigration>> {
+        let mut migrations = Vec::new();
+
+        for entry in migrations_directories(&self.directory)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if version_from_string(&name).is_none() {
+                continue;
+            }
+
+            let up = fs::read_to_string(entry.path().join("up.sql"))?;
+            let down = fs::read_to_string(entry.path().join("down.sql")).ok();
+
+            let run_in_transaction = !first_line_is_no_transaction_annotation(&up)
+                && !down
+                    .as_deref()
+                    .is_some_and(first_line_is_no_transaction_annotation);
+
+            migrations.push(FileMigration {
+                name,
+                up,
+                down,
+                run_in_transaction,
+            });
+        }
+
+        Ok(migrations)
+    }
+
+    fn setup_attempted(&self) -> &AtomicU8 {
+        &self.setup_attempted
+    }
+}
+
+fn migrations_directories(
+    path: &Path,
+) -> std::io::Result<impl Iterator<Item = std::io::Result<fs::DirEntry>> + '_> {
+    Ok(path.read_dir()?.filter_map(|entry_res| {
+        entry_res
+            .and_then(|entry| {
+                Ok(
+                    if entry.metadata()?.is_file()
+                        || entry.file_name().to_string_lossy().starts_with('.')
+                    {
+                        None
+                    } else {
+                        Some(entry)
+                    },
+                )
+            })
+            .transpose()
+    }))
+}
+
+fn version_from_string(path: &str) -> Option<String> {
+    path.split('_').next().map(|s| s.replace('-', ""))
+}
+
+fn search_for_migrations_directory(path: &Path) -> Option<PathBuf> {
+    let migration_path = path.join("migrations");
+    if migration_path.is_dir() {
+        Some(migration_path)
+    } else {
+        path.parent().and_then(search_for_migrations_directory)
+    }
+}
+
+fn first_line_is_no_transaction_annotation(sql: &str) -> bool {
+    sql.lines()
+        .next()
+        .is_some_and(|line| line.trim() == NO_TRANSACTION_ANNOTATION)
+}
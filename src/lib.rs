@@ -1,87 +1,349 @@
 use std::{
     collections::HashMap,
+    fmt,
     sync::atomic::{AtomicU8, Ordering},
 };
 
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use diesel_async::{AsyncConnection, RunQueryDsl};
 pub use diesel_async_migrations_macros::embed_migrations;
 use scoped_futures::ScopedFutureExt;
+use sha2::{Digest, Sha256};
 use tracing::info;
 
+mod backend;
+mod file;
+
+pub use backend::{MigrationBackend, TransactionalDdl};
+pub use file::{FileBasedMigrations, FileMigration};
+
 diesel::table! {
     __diesel_schema_migrations (version) {
         version -> VarChar,
         run_on -> Timestamp,
+        checksum -> Nullable<Binary>,
+    }
+}
+
+type Result<T> = std::result::Result<T, MigrationError>;
+
+/// Errors produced while inspecting or applying migrations.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// A database error occurred while running or recording a migration.
+    Diesel(diesel::result::Error),
+    /// A migration that was already applied no longer matches the checksum
+    /// recorded when it ran, i.e. its `up`/`down` SQL was edited after the
+    /// fact.
+    ChecksumMismatch {
+        /// Version of the migration whose checksum no longer matches.
+        version: String,
+    },
+    /// A migration flagged as non-transactional (e.g. for
+    /// `CREATE INDEX CONCURRENTLY`) was encountered while applying pending
+    /// migrations inside a single enclosing transaction; it can't
+    /// participate in one.
+    NonTransactional {
+        /// Version of the non-transactional migration.
+        version: String,
+    },
+    /// An I/O error occurred while discovering or reading migrations from
+    /// disk (only produced by [`FileBasedMigrations`]).
+    Io(std::io::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Diesel(e) => e.fmt(f),
+            MigrationError::ChecksumMismatch { version } => write!(
+                f,
+                "migration {version} was modified after being applied (checksum mismatch)"
+            ),
+            MigrationError::NonTransactional { version } => write!(
+                f,
+                "migration {version} is flagged non-transactional and can't be applied inside a single enclosing transaction"
+            ),
+            MigrationError::Io(e) => e.fmt(f),
+        }
     }
 }
 
-type Result<T> = std::result::Result<T, diesel::result::Error>;
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrationError::Diesel(e) => Some(e),
+            MigrationError::Io(e) => Some(e),
+            MigrationError::ChecksumMismatch { .. } | MigrationError::NonTransactional { .. } => {
+                None
+            }
+        }
+    }
+}
+
+impl From<diesel::result::Error> for MigrationError {
+    fn from(e: diesel::result::Error) -> Self {
+        MigrationError::Diesel(e)
+    }
+}
+
+impl From<std::io::Error> for MigrationError {
+    fn from(e: std::io::Error) -> Self {
+        MigrationError::Io(e)
+    }
+}
+
+/// Session-level advisory lock key used to serialize concurrent migration runs
+/// against the same database. Derived at compile time from the schema
+/// migrations table name (FNV-1a) so that every instance of this crate
+/// pointed at the same database agrees on the key, without requiring any
+/// configuration.
+const ADVISORY_LOCK_KEY: i64 = {
+    const fn fnv1a(bytes: &[u8]) -> u64 {
+        let mut hash = 0xcbf29ce484222325u64;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            i += 1;
+        }
+        hash
+    }
+
+    // Mask off the sign bit so the hash always fits in a `bigint`.
+    (fnv1a(b"__diesel_schema_migrations") & 0x7fff_ffff_ffff_ffff) as i64
+};
+
+#[cfg(feature = "postgres")]
+diesel::sql_function! {
+    fn pg_advisory_lock(key: diesel::sql_types::BigInt);
+}
+
+#[cfg(feature = "postgres")]
+diesel::sql_function! {
+    fn pg_advisory_unlock(key: diesel::sql_types::BigInt) -> diesel::sql_types::Bool;
+}
+
+// Postgres-only: session-level advisory locks have no equivalent this crate
+// relies on for MySQL or SQLite.
+#[cfg(feature = "postgres")]
+async fn acquire_advisory_lock<C>(conn: &mut C) -> Result<()>
+where
+    C: AsyncConnection<Backend = diesel::pg::Pg>,
+{
+    diesel::select(pg_advisory_lock(ADVISORY_LOCK_KEY))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+async fn release_advisory_lock<C>(conn: &mut C) -> Result<()>
+where
+    C: AsyncConnection<Backend = diesel::pg::Pg>,
+{
+    diesel::select(pg_advisory_unlock(ADVISORY_LOCK_KEY))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Pairs [`acquire_advisory_lock`] with [`release_advisory_lock`], so the
+/// lock is released on both the success and error path of whatever ran
+/// while it was held.
+///
+/// This is *not* a full RAII guard: releasing the lock needs an async round
+/// trip to the database, which `Drop` can't perform, so there is no way to
+/// release it if the future holding it is cancelled rather than run to
+/// completion. See the cancellation hazard documented on
+/// [`run_pending_migrations`](MigrationSource::run_pending_migrations) and
+/// [`revert_last_migration`](MigrationSource::revert_last_migration).
+#[cfg(feature = "postgres")]
+struct AdvisoryLockGuard;
+
+#[cfg(feature = "postgres")]
+impl AdvisoryLockGuard {
+    async fn acquire<C>(conn: &mut C) -> Result<Self>
+    where
+        C: AsyncConnection<Backend = diesel::pg::Pg>,
+    {
+        acquire_advisory_lock(conn).await?;
+        Ok(Self)
+    }
+
+    /// Releases the lock, preferring to surface `res` (the result of the
+    /// work done while holding it) over a failure to release, since the
+    /// caller cares more about that than about the unlock call.
+    async fn release<C>(self, conn: &mut C, res: Result<()>) -> Result<()>
+    where
+        C: AsyncConnection<Backend = diesel::pg::Pg>,
+    {
+        let release_res = release_advisory_lock(conn).await;
+        res.and(release_res)
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct EmbeddedMigration {
     pub up: &'static str,
     pub down: Option<&'static str>,
     pub name: &'static str,
+    /// Whether `up`/`down` should be run inside a transaction. `false` for
+    /// migrations whose first line is the
+    /// `-- diesel_async_migrations:no-transaction` annotation, needed for
+    /// statements such as `CREATE INDEX CONCURRENTLY` that Postgres refuses
+    /// to run inside a transaction block.
+    pub run_in_transaction: bool,
 }
 
-impl EmbeddedMigration {
-    pub fn version(&self) -> String {
+impl Migration for EmbeddedMigration {
+    fn name(&self) -> &str {
         self.name
+    }
+
+    fn up(&self) -> &str {
+        self.up
+    }
+
+    fn down(&self) -> Option<&str> {
+        self.down
+    }
+
+    fn run_in_transaction(&self) -> bool {
+        self.run_in_transaction
+    }
+}
+
+/// A single migration, independent of where it was loaded from ([`EmbeddedMigration`]
+/// for `embed_migrations!`, [`FileMigration`] for [`FileBasedMigrations`]).
+pub trait Migration {
+    fn name(&self) -> &str;
+    fn up(&self) -> &str;
+    fn down(&self) -> Option<&str>;
+
+    /// Whether `up`/`down` should be run inside a transaction. `false` for
+    /// migrations whose first line is the
+    /// `-- diesel_async_migrations:no-transaction` annotation, needed for
+    /// statements such as `CREATE INDEX CONCURRENTLY` that Postgres refuses
+    /// to run inside a transaction block.
+    fn run_in_transaction(&self) -> bool;
+
+    fn version(&self) -> String {
+        self.name()
             .split('_')
             .next()
             .map(|s| s.replace('-', ""))
             .expect("invalid migration name")
     }
 
-    pub async fn run<C>(&self, conn: &mut C) -> Result<Version>
+    /// SHA-256 over this migration's `up` SQL (and `down` SQL, if present),
+    /// used to detect whether an already-applied migration was edited.
+    fn checksum(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.up().as_bytes());
+        if let Some(down) = self.down() {
+            hasher.update(down.as_bytes());
+        }
+        hasher.finalize().to_vec()
+    }
+
+    fn run<C>(&self, conn: &mut C) -> impl std::future::Future<Output = Result<Version>> + Send
     where
-        C: AsyncConnection<Backend = diesel::pg::Pg> + 'static + Send,
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
     {
-        let qry = self.up.to_string();
-        let version = self.version();
-        let res = conn
-            .transaction::<_, diesel::result::Error, _>(|conn| {
-                async move {
-                    conn.batch_execute(&qry).await?;
-
-                    let version = diesel::insert_into(__diesel_schema_migrations::table)
-                        .values(__diesel_schema_migrations::version.eq(version))
-                        .returning(__diesel_schema_migrations::version)
-                        .get_result::<String>(conn)
-                        .await?;
-
-                    Ok(Version { version })
-                }
-                .scope_boxed()
-            })
-            .await?;
-
-        Ok(res)
+        async move {
+            let version = self.version();
+            let checksum = self.checksum();
+            let qry = self.up().to_string();
+
+            if self.run_in_transaction() {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move {
+                        conn.batch_execute(&qry).await?;
+                        insert_version(conn, version, checksum).await
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(MigrationError::from)
+            } else {
+                // Run outside of a transaction block (e.g. for
+                // `CREATE INDEX CONCURRENTLY`), then record the applied
+                // version in its own, separate transaction.
+                conn.batch_execute(&qry).await?;
+
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move { insert_version(conn, version, checksum).await }.scope_boxed()
+                })
+                .await
+                .map_err(MigrationError::from)
+            }
+        }
     }
 
-    pub async fn revert<C>(&self, conn: &mut C) -> Result<Version>
+    fn revert<C>(&self, conn: &mut C) -> impl std::future::Future<Output = Result<Version>> + Send
     where
-        C: AsyncConnection<Backend = diesel::pg::Pg> + 'static + Send,
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
     {
-        conn.transaction::<_, diesel::result::Error, _>(|conn| {
-            async move {
-                conn.batch_execute(self.down.unwrap_or_default()).await?;
-
-                diesel::delete(__diesel_schema_migrations::table.find(self.version()))
-                    .execute(conn)
-                    .await?;
+        async move {
+            let version = self.version();
+            let down = self.down().unwrap_or_default().to_string();
+
+            if self.run_in_transaction() {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move {
+                        conn.batch_execute(&down).await?;
+                        delete_version(conn, &version).await
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(MigrationError::from)
+            } else {
+                conn.batch_execute(&down).await?;
 
-                Ok(Version {
-                    version: self.version(),
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move { delete_version(conn, &version).await }.scope_boxed()
                 })
+                .await
+                .map_err(MigrationError::from)
             }
-            .scope_boxed()
-        })
-        .await
+        }
     }
 }
 
+// Backend-specific: MySQL has no `RETURNING` clause, so recording/removing a
+// migration can't be expressed as one generic diesel query across every
+// backend. See `MigrationBackend::insert_version`/`delete_version`.
+
+async fn insert_version<C>(
+    conn: &mut C,
+    version: String,
+    checksum: Vec<u8>,
+) -> std::result::Result<Version, diesel::result::Error>
+where
+    C: AsyncConnection + Send,
+    C::Backend: MigrationBackend,
+{
+    <C::Backend as MigrationBackend>::insert_version(conn, version, checksum).await
+}
+
+async fn delete_version<C>(
+    conn: &mut C,
+    version: &str,
+) -> std::result::Result<Version, diesel::result::Error>
+where
+    C: AsyncConnection + Send,
+    C::Backend: MigrationBackend,
+{
+    <C::Backend as MigrationBackend>::delete_version(conn, version).await
+}
+
 #[allow(missing_copy_implementations)]
 #[derive(Debug)]
 pub struct EmbeddedMigrations {
@@ -89,111 +351,472 @@ pub struct EmbeddedMigrations {
     pub setup_attempted: AtomicU8,
 }
 
-impl EmbeddedMigrations {
-    pub async fn setup_migrations_table<C>(&self, conn: &mut C) -> Result<()>
+/// A set of migrations that can be applied to, or reverted from, a database.
+///
+/// Implemented by [`EmbeddedMigrations`] (the `embed_migrations!` macro
+/// output) and [`FileBasedMigrations`] (migrations discovered at runtime).
+/// Applying, reverting, and listing migrations is implemented once here and
+/// shared between both.
+pub trait MigrationSource {
+    /// The concrete migration type this source yields.
+    type Migration: Migration + Clone + Send + 'static;
+
+    /// Lists every migration known to this source, in no particular order.
+    fn migrations(&self) -> Result<Vec<Self::Migration>>;
+
+    /// Tracks whether [`setup_migrations_table`](Self::setup_migrations_table)
+    /// has already run once for this process.
+    fn setup_attempted(&self) -> &AtomicU8;
+
+    fn setup_migrations_table<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
     where
-        C: AsyncConnection<Backend = diesel::pg::Pg>,
+        C: AsyncConnection + Send,
+        C::Backend: MigrationBackend,
     {
-        conn.batch_execute(include_str!("setup_migration_table.sql"))
-            .await?;
+        async move {
+            conn.batch_execute(<C::Backend as MigrationBackend>::SETUP_MIGRATIONS_TABLE_SQL)
+                .await?;
+            <C::Backend as MigrationBackend>::ensure_checksum_column(conn).await?;
 
-        Ok(())
+            Ok(())
+        }
     }
 
-    async fn ensure_migrations_table<C>(&self, conn: &mut C) -> Result<()>
+    fn ensure_migrations_table<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
     where
-        C: AsyncConnection<Backend = diesel::pg::Pg>,
+        C: AsyncConnection + Send,
+        C::Backend: MigrationBackend,
     {
-        if self.setup_attempted.fetch_add(1, Ordering::SeqCst) == 0 {
-            self.setup_migrations_table(conn).await?;
-        }
+        async move {
+            if self.setup_attempted().fetch_add(1, Ordering::SeqCst) == 0 {
+                self.setup_migrations_table(conn).await?;
+            }
 
-        Ok(())
+            Ok(())
+        }
     }
 
-    pub async fn run_pending_migrations<C>(&self, conn: &mut C) -> Result<()>
+    /// Applies all pending migrations, holding a Postgres session-level
+    /// advisory lock for the duration of the run so that multiple instances
+    /// booting concurrently serialize against each other instead of racing.
+    ///
+    /// If your connections go through pgbouncer in transaction pooling mode,
+    /// session-level advisory locks don't work (the session backing a
+    /// connection can change between statements), so use
+    /// [`run_pending_migrations_without_lock`](Self::run_pending_migrations_without_lock)
+    /// instead and synchronize migrations some other way.
+    ///
+    /// This locking overload is only available with the `postgres` feature:
+    /// MySQL and SQLite have no equivalent session-level advisory lock, so
+    /// there is nothing to take it out on. Without the `postgres` feature,
+    /// `run_pending_migrations` instead resolves to the non-locking overload
+    /// below.
+    ///
+    /// # Cancellation hazard
+    ///
+    /// Releasing the advisory lock needs an async round trip to the
+    /// database, so it can only happen if this future is polled to
+    /// completion. If it's dropped early instead — a `tokio::time::timeout`
+    /// firing, losing a `select!` race, a task being aborted — the lock
+    /// stays held for the lifetime of the underlying database session. A
+    /// pooled connection returned to the pool in that state will make every
+    /// later migration run that checks out that session hang waiting for
+    /// the lock (the session only releases it by disconnecting). Don't
+    /// cancel this future; if you must bound it with a timeout, close the
+    /// connection rather than returning it to the pool when the timeout
+    /// fires.
+    #[cfg(feature = "postgres")]
+    fn run_pending_migrations<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
     where
         C: AsyncConnection<Backend = diesel::pg::Pg> + 'static + Send,
     {
-        self.ensure_migrations_table(conn).await?;
+        async move {
+            let guard = AdvisoryLockGuard::acquire(conn).await?;
+            let res = self.run_pending_migrations_without_lock(conn).await;
+            guard.release(conn, res).await
+        }
+    }
 
-        let pending_migs = self.pending_migrations(conn).await?;
+    /// Applies all pending migrations. An alias for
+    /// [`run_pending_migrations_without_lock`](Self::run_pending_migrations_without_lock)
+    /// under this name so that MySQL and SQLite users — who, without the
+    /// `postgres` feature, don't have the advisory-lock-taking overload
+    /// above — have an obviously-named entry point instead of having to
+    /// discover `_without_lock` and wonder what safety feature they're
+    /// opting out of. Neither backend has a session-level advisory lock for
+    /// this to take out in the first place; callers are responsible for
+    /// ensuring only one instance runs migrations at a time.
+    #[cfg(not(feature = "postgres"))]
+    fn run_pending_migrations<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
+    {
+        self.run_pending_migrations_without_lock(conn)
+    }
 
-        if pending_migs.is_empty() {
-            info!("no pending migrations");
-        } else {
-            info!("applying {} pending migrations", pending_migs.len());
-        }
+    /// Same as [`run_pending_migrations`](Self::run_pending_migrations), but
+    /// without taking the advisory lock. Intended for setups (e.g. pgbouncer
+    /// transaction pooling) where session-level advisory locks aren't usable;
+    /// callers are responsible for ensuring only one instance runs migrations
+    /// at a time.
+    fn run_pending_migrations_without_lock<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
+    {
+        async move {
+            self.ensure_migrations_table(conn).await?;
+
+            let pending_migs = self.pending_migrations(conn).await?;
 
-        for mig in pending_migs {
-            info!("applying migration {}", mig.name);
-            mig.run(conn).await?;
+            if pending_migs.is_empty() {
+                info!("no pending migrations");
+            } else {
+                info!("applying {} pending migrations", pending_migs.len());
+            }
+
+            for mig in pending_migs {
+                info!("applying migration {}", mig.name());
+                mig.run(conn).await?;
+            }
+
+            Ok(())
         }
+    }
+
+    /// Applies all pending migrations inside a single outer transaction, so
+    /// that a failure partway through rolls every migration in the batch
+    /// back instead of leaving the database partially migrated.
+    ///
+    /// Returns [`MigrationError::NonTransactional`] without applying
+    /// anything if any pending migration is flagged
+    /// `run_in_transaction = false` (see the `-- diesel_async_migrations:no-transaction`
+    /// annotation), since those can't participate in an enclosing
+    /// transaction.
+    ///
+    /// Only available for backends where [`TransactionalDdl`] is
+    /// implemented (Postgres, SQLite). MySQL's DDL statements trigger an
+    /// implicit commit, so the roll-back-on-failure guarantee above doesn't
+    /// hold there; use
+    /// [`run_pending_migrations_without_lock`](Self::run_pending_migrations_without_lock)
+    /// (or [`run_pending_migrations`](Self::run_pending_migrations)) instead,
+    /// which apply and record each migration in its own transaction.
+    fn run_pending_migrations_in_transaction<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: TransactionalDdl,
+    {
+        async move {
+            self.ensure_migrations_table(conn).await?;
 
-        Ok(())
+            let pending_migs = self.pending_migrations(conn).await?;
+
+            if let Some(mig) = pending_migs.iter().find(|mig| !mig.run_in_transaction()) {
+                return Err(MigrationError::NonTransactional {
+                    version: mig.version(),
+                });
+            }
+
+            if pending_migs.is_empty() {
+                info!("no pending migrations");
+                return Ok(());
+            }
+
+            info!(
+                "applying {} pending migrations in a single transaction",
+                pending_migs.len()
+            );
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    for mig in &pending_migs {
+                        info!("applying migration {}", mig.name());
+
+                        let qry = mig.up().to_string();
+                        conn.batch_execute(&qry).await?;
+
+                        insert_version(conn, mig.version(), mig.checksum()).await?;
+                    }
+
+                    Ok(())
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(MigrationError::from)
+        }
     }
 
-    pub async fn revert_last_migration<C>(&self, conn: &mut C) -> Result<()>
+    /// Reverts the most recently applied migration, holding the same
+    /// advisory lock as [`run_pending_migrations`](Self::run_pending_migrations)
+    /// so it can't race with a concurrent migration run.
+    ///
+    /// This locking overload is only available with the `postgres` feature;
+    /// see [`run_pending_migrations`](Self::run_pending_migrations).
+    ///
+    /// # Cancellation hazard
+    ///
+    /// Shares the advisory lock with
+    /// [`run_pending_migrations`](Self::run_pending_migrations); see the
+    /// cancellation hazard documented there. It applies here too.
+    #[cfg(feature = "postgres")]
+    fn revert_last_migration<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
     where
         C: AsyncConnection<Backend = diesel::pg::Pg> + 'static + Send,
     {
-        if let Some(last_migration_version) = get_applied_migrations(conn).await?.into_iter().next()
-        {
-            if let Some(migration_to_revert) = self
-                .migrations
-                .iter()
-                .find(|m| m.version() == *last_migration_version.version)
+        async move {
+            let guard = AdvisoryLockGuard::acquire(conn).await?;
+            let res = self.revert_last_migration_without_lock(conn).await;
+            guard.release(conn, res).await
+        }
+    }
+
+    /// Reverts the most recently applied migration. An alias for
+    /// [`revert_last_migration_without_lock`](Self::revert_last_migration_without_lock)
+    /// under this name; see
+    /// [`run_pending_migrations`](Self::run_pending_migrations) for why this
+    /// overload exists for MySQL and SQLite.
+    #[cfg(not(feature = "postgres"))]
+    fn revert_last_migration<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
+    {
+        self.revert_last_migration_without_lock(conn)
+    }
+
+    /// Same as [`revert_last_migration`](Self::revert_last_migration), but
+    /// without taking the advisory lock (see
+    /// [`run_pending_migrations_without_lock`](Self::run_pending_migrations_without_lock)).
+    fn revert_last_migration_without_lock<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<()>> + Send
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
+    {
+        async move {
+            let migrations = self.migrations()?;
+
+            if let Some(last_migration_version) =
+                get_applied_migrations(conn).await?.into_iter().next()
+            {
+                if let Some(migration_to_revert) = migrations
+                    .iter()
+                    .find(|m| m.version() == *last_migration_version.version)
+                {
+                    migration_to_revert.revert(conn).await?;
+                    return Ok(());
+                }
+            }
+            Err(MigrationError::Diesel(diesel::result::Error::NotFound))
+        }
+    }
+
+    /// Reverts applied migrations, most recent first, down to (but not
+    /// including) `target_version`. Does nothing if `target_version` is the
+    /// currently applied version. Returns an error if `target_version` has
+    /// never been applied, or isn't a version of any known migration.
+    fn revert_to_version<'a, C>(
+        &'a self,
+        conn: &'a mut C,
+        target_version: &'a str,
+    ) -> impl std::future::Future<Output = Result<()>> + Send + 'a
+    where
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
+    {
+        async move {
+            let migrations = self.migrations()?;
+            let applied = get_applied_migrations(conn).await?;
+
+            if !applied.iter().any(|v| v.version == target_version)
+                || !migrations.iter().any(|m| m.version() == target_version)
             {
+                return Err(MigrationError::Diesel(diesel::result::Error::NotFound));
+            }
+
+            for applied_version in applied {
+                if applied_version.version == target_version {
+                    break;
+                }
+
+                let migration_to_revert = migrations
+                    .iter()
+                    .find(|m| m.version() == applied_version.version)
+                    .ok_or(MigrationError::Diesel(diesel::result::Error::NotFound))?;
+
                 migration_to_revert.revert(conn).await?;
-                return Ok(());
             }
+
+            Ok(())
         }
-        Err(diesel::result::Error::NotFound)
     }
 
-    pub async fn pending_migrations<C>(&self, conn: &mut C) -> Result<Vec<EmbeddedMigration>>
+    /// Reverts every applied migration, leaving the database as if none of
+    /// them had ever run.
+    fn revert_all<C>(&self, conn: &mut C) -> impl std::future::Future<Output = Result<()>> + Send
     where
-        C: AsyncConnection<Backend = diesel::pg::Pg>,
+        C: AsyncConnection + 'static + Send,
+        C::Backend: MigrationBackend,
     {
-        self.ensure_migrations_table(conn).await?;
+        async move {
+            // Stop only once nothing is recorded as applied; an applied
+            // version with no known migration is an error, not "done".
+            while !get_applied_migrations(conn).await?.is_empty() {
+                self.revert_last_migration_without_lock(conn).await?;
+            }
 
-        let applied_versions = get_applied_migrations(conn).await?;
+            Ok(())
+        }
+    }
 
-        let mut migrations = self
-            .migrations
-            .iter()
-            .map(|mig| (mig.version(), *mig))
-            .collect::<HashMap<_, _>>();
+    /// Returns every known migration paired with whether it has been
+    /// applied (and, if so, when), ordered by version. A programmatic
+    /// equivalent of a `migration list`/status report.
+    fn migration_status<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<Vec<MigrationStatus<Self::Migration>>>> + Send
+    where
+        C: AsyncConnection + Send,
+        C::Backend: MigrationBackend,
+    {
+        async move {
+            self.ensure_migrations_table(conn).await?;
+
+            let migrations = self.migrations()?;
+
+            let applied_by_version = get_applied_migrations(conn)
+                .await?
+                .into_iter()
+                .map(|v| (v.version.clone(), v))
+                .collect::<HashMap<_, _>>();
+
+            let mut statuses = migrations
+                .into_iter()
+                .map(|mig| {
+                    let applied = applied_by_version.get(&mig.version());
+
+                    MigrationStatus {
+                        applied: applied.is_some(),
+                        run_on: applied.map(|v| v.run_on),
+                        migration: mig,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            statuses.sort_unstable_by_key(|status| status.migration.version());
+
+            Ok(statuses)
+        }
+    }
+
+    fn pending_migrations<C>(
+        &self,
+        conn: &mut C,
+    ) -> impl std::future::Future<Output = Result<Vec<Self::Migration>>> + Send
+    where
+        C: AsyncConnection + Send,
+        C::Backend: MigrationBackend,
+    {
+        async move {
+            self.ensure_migrations_table(conn).await?;
+
+            let applied_versions = get_applied_migrations(conn).await?;
+
+            let mut migrations = self
+                .migrations()?
+                .into_iter()
+                .map(|mig| (mig.version(), mig))
+                .collect::<HashMap<_, _>>();
+
+            for applied_version in &applied_versions {
+                // Migrations applied before this crate recorded checksums
+                // have `checksum == NULL`; there's nothing to verify them
+                // against.
+                if let Some(stored_checksum) = &applied_version.checksum {
+                    if let Some(mig) = migrations.get(&applied_version.version) {
+                        if &mig.checksum() != stored_checksum {
+                            return Err(MigrationError::ChecksumMismatch {
+                                version: applied_version.version.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            for applied_version in applied_versions {
+                migrations.remove(&applied_version.version);
+            }
 
-        for applied_version in applied_versions {
-            migrations.remove(&applied_version.version);
+            let mut migrations = migrations.into_values().collect::<Vec<_>>();
+
+            migrations.sort_unstable_by_key(|mig| mig.version());
+
+            Ok(migrations)
         }
+    }
+}
 
-        let mut migrations = migrations.into_values().collect::<Vec<_>>();
+impl MigrationSource for EmbeddedMigrations {
+    type Migration = EmbeddedMigration;
 
-        migrations.sort_unstable_by_key(|mig| mig.version());
+    fn migrations(&self) -> Result<Vec<EmbeddedMigration>> {
+        Ok(self.migrations.to_vec())
+    }
 
-        Ok(migrations)
+    fn setup_attempted(&self) -> &AtomicU8 {
+        &self.setup_attempted
     }
 }
 
 #[derive(Queryable)]
 pub struct Version {
     version: String,
+    run_on: NaiveDateTime,
+    checksum: Option<Vec<u8>>,
+}
+
+/// A migration paired with whether it has been applied, and when.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus<M> {
+    pub migration: M,
+    pub applied: bool,
+    /// `None` unless `applied` is `true`.
+    pub run_on: Option<NaiveDateTime>,
 }
 
 async fn get_applied_migrations<C>(conn: &mut C) -> Result<Vec<Version>>
 where
-    C: AsyncConnection<Backend = diesel::pg::Pg>,
+    C: AsyncConnection + Send,
+    C::Backend: MigrationBackend,
 {
-    let res = __diesel_schema_migrations::table
-        .select(__diesel_schema_migrations::version)
-        .order(__diesel_schema_migrations::version.desc())
-        .get_results::<String>(conn)
-        .await?
-        .into_iter()
-        .map(|version| Version { version })
-        .collect::<Vec<_>>();
-
-    Ok(res)
+    Ok(<C::Backend as MigrationBackend>::applied_migrations(conn).await?)
 }